@@ -0,0 +1,61 @@
+//! Picks, among the devicetree blobs embedded in the running UKI, the
+//! one matching the firmware's own platform, so a single image can
+//! support multiple boards without the user pre-selecting a DTB.
+
+use uefi::table::{Boot, SystemTable};
+
+use crate::fdt_loader::{blob_from_ptr, root_compatible_strings, DEVICE_TREE_GUID};
+use crate::uefi_helpers::PeInMemory;
+
+/// Section holding each auto-selected candidate devicetree. Multiple
+/// sections may share this name.
+const AUTO_DTB_SECTION: &str = ".dtbauto";
+/// Section holding the single, unconditional default devicetree.
+const DEFAULT_DTB_SECTION: &str = ".dtb";
+
+/// Selects the embedded devicetree blob whose first `compatible`
+/// string matches the firmware's own platform devicetree (read from
+/// the devicetree configuration table, if firmware publishes one),
+/// falling back to the image's `.dtb` section.
+///
+/// # Safety
+///
+/// `image` must describe the currently running, unmutated image, per
+/// the requirements of [`PeInMemory::sections`].
+pub unsafe fn select_dtb(
+    image: &PeInMemory,
+    system_table: &SystemTable<Boot>,
+) -> Option<&'static [u8]> {
+    let sections = unsafe { image.sections() };
+
+    if let Some(wanted) = firmware_root_compatible(system_table) {
+        let matching = sections.iter().find(|section| {
+            section.name() == AUTO_DTB_SECTION
+                && root_compatible_strings(section.data).and_then(|c| c.first().copied())
+                    == Some(wanted)
+        });
+        if let Some(section) = matching {
+            return Some(section.data);
+        }
+    }
+
+    sections
+        .iter()
+        .find(|section| section.name() == DEFAULT_DTB_SECTION)
+        .map(|section| section.data)
+}
+
+/// Returns the firmware's own platform devicetree's first
+/// `compatible` string, if firmware publishes one via the devicetree
+/// configuration table.
+fn firmware_root_compatible(system_table: &SystemTable<Boot>) -> Option<&'static str> {
+    let entry = system_table
+        .config_table()
+        .iter()
+        .find(|entry| entry.guid == DEVICE_TREE_GUID)?;
+
+    // SAFETY: a present `DEVICE_TREE_GUID` entry's address, per the
+    // UEFI specification, points at a valid device tree blob.
+    let blob = unsafe { blob_from_ptr(entry.address.cast()) }?;
+    root_compatible_strings(blob)?.first().copied()
+}
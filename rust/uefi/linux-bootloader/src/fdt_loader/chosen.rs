@@ -0,0 +1,199 @@
+//! Domain-specific edits to the FDT's `/chosen` node: passing boot
+//! parameters to the kernel and keeping TPM measurements of the tree
+//! reproducible.
+
+use alloc::vec::Vec;
+
+use uefi::{prelude::BootServices, proto::rng::Rng, table::boot::ScopedProtocol, Result, Status, StatusExt};
+
+use super::raw;
+
+/// Strips `kaslr-seed` from `/chosen`, if present.
+///
+/// The EFI stub ignores `kaslr-seed` (it physically randomizes via
+/// `EFI_RNG_PROTOCOL` instead), but its random bytes would make the
+/// DTB measurement lanzaboote feeds to the TPM irreproducible across
+/// boots. Safe to call whether or not `/chosen` exists yet.
+pub(super) fn strip_kaslr_seed(buf: &mut [u8]) {
+    delete_chosen_property(buf, "kaslr-seed");
+}
+
+/// Reseeds `/chosen/rng-seed` with freshly drawn randomness, if an
+/// RNG protocol is available, so the kernel entropy pool lost to
+/// [`strip_kaslr_seed`] isn't lost for good while keeping what we
+/// measure under our own control.
+///
+/// Must run after `/chosen` exists (i.e. after
+/// [`write_boot_params`]): appending a property to a node that
+/// doesn't exist yet is a no-op, so calling this any earlier would
+/// silently drop the seed on every board whose DTB doesn't already
+/// ship a `/chosen` node.
+pub(super) fn reseed_rng_seed(boot_services: &BootServices, buf: &mut [u8]) {
+    let Some(mut rng) = open_rng_protocol(boot_services) else {
+        return;
+    };
+    let mut seed = [0u8; 32];
+    if rng.get_rng(None, &mut seed).is_ok() {
+        // Best-effort: if there isn't room left, the kernel simply
+        // doesn't get a seed via the DTB.
+        let _ = append_chosen_property(buf, "rng-seed", &seed);
+    }
+}
+
+fn open_rng_protocol(boot_services: &BootServices) -> Option<ScopedProtocol<Rng>> {
+    let handle = boot_services.get_handle_for_protocol::<Rng>().ok()?;
+    boot_services.open_protocol_exclusive::<Rng>(handle).ok()
+}
+
+/// Creates `/chosen` if it doesn't already exist, then sets
+/// `bootargs` to `cmdline` and, when `initrd_range` (start, end) is
+/// given, `linux,initrd-start` / `linux,initrd-end` as big-endian
+/// cells sized per the root node's `#address-cells`. Mirrors the
+/// Linux EFI stub's `update_fdt`.
+pub(super) fn write_boot_params(
+    buf: &mut [u8],
+    cmdline: &str,
+    initrd_range: Option<(u64, u64)>,
+) -> Result<()> {
+    if raw::find_node(buf, "/chosen").is_none() {
+        let Some((_, root_end)) = raw::find_node(buf, "/") else {
+            return Status::INVALID_PARAMETER.to_result();
+        };
+        if raw::create_node(buf, root_end, "chosen").is_none() {
+            return Status::OUT_OF_RESOURCES.to_result();
+        }
+    }
+
+    delete_chosen_property(buf, "bootargs");
+    let mut bootargs = Vec::with_capacity(cmdline.len() + 1);
+    bootargs.extend_from_slice(cmdline.as_bytes());
+    bootargs.push(0);
+    append_chosen_property(buf, "bootargs", &bootargs)?;
+
+    if let Some((start, end)) = initrd_range {
+        // The devicetree specification defaults the root node's
+        // `#address-cells` to 2 when the property is absent.
+        let cells = raw::root_address_cells(buf).unwrap_or(2).clamp(1, 2);
+
+        delete_chosen_property(buf, "linux,initrd-start");
+        delete_chosen_property(buf, "linux,initrd-end");
+
+        append_chosen_property(buf, "linux,initrd-start", &encode_cells(start, cells))?;
+        append_chosen_property(buf, "linux,initrd-end", &encode_cells(end, cells))?;
+    }
+
+    Ok(())
+}
+
+fn delete_chosen_property(buf: &mut [u8], name: &str) {
+    if let Some((start, end)) = raw::find_node(buf, "/chosen") {
+        if let Some((offset, len)) = raw::find_property(buf, start, end, name) {
+            raw::delete_property(buf, offset, len);
+        }
+    }
+}
+
+fn append_chosen_property(buf: &mut [u8], name: &str, value: &[u8]) -> Result<()> {
+    let Some((_, end)) = raw::find_node(buf, "/chosen") else {
+        return Status::INVALID_PARAMETER.to_result();
+    };
+    if raw::append_property(buf, end, name, value).is_none() {
+        return Status::OUT_OF_RESOURCES.to_result();
+    }
+    Ok(())
+}
+
+/// Encodes `value` as `cells` big-endian 32-bit cells (1 or 2).
+fn encode_cells(value: u64, cells: u32) -> Vec<u8> {
+    if cells <= 1 {
+        (value as u32).to_be_bytes().to_vec()
+    } else {
+        value.to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// `reseed_rng_seed` itself needs a `BootServices` handle to look
+    /// up `EFI_RNG_PROTOCOL`, which isn't mockable here, but
+    /// `strip_kaslr_seed` is plain FDT editing and exercises the
+    /// exact append/delete path that used to be dead due to the
+    /// `relocate` totalsize bug.
+    #[test]
+    fn kaslr_seed_is_removed_from_chosen() {
+        let src = raw::test_fdt_blob();
+        let mut dst = vec![0u8; src.len() + 512];
+        raw::relocate(&src, &mut dst);
+
+        let (_, root_end) = raw::find_node(&dst, "/").unwrap();
+        let (_, chosen_end) = raw::create_node(&mut dst, root_end, "chosen").unwrap();
+        raw::append_property(&mut dst, chosen_end, "kaslr-seed", &[0u8; 8]).unwrap();
+
+        strip_kaslr_seed(&mut dst);
+
+        let (start, end) = raw::find_node(&dst, "/chosen").unwrap();
+        assert!(raw::find_property(&dst, start, end, "kaslr-seed").is_none());
+    }
+
+    /// Mirrors `FdtLoader::new`'s real ordering (strip, then create
+    /// `/chosen` via `write_boot_params`, then reseed) on a blob that
+    /// starts with no `/chosen` node at all — `test_fdt_blob` is one,
+    /// like most board DTBs. Regression test for a maintainer review
+    /// finding: reseeding used to run *before* `write_boot_params`
+    /// created `/chosen`, so `append_chosen_property` always failed
+    /// with `INVALID_PARAMETER` and the seed was silently dropped.
+    #[test]
+    fn rng_seed_can_be_appended_once_chosen_is_created() {
+        let src = raw::test_fdt_blob();
+        let mut dst = vec![0u8; src.len() + 512];
+        raw::relocate(&src, &mut dst);
+        assert!(raw::find_node(&dst, "/chosen").is_none());
+
+        strip_kaslr_seed(&mut dst);
+        write_boot_params(&mut dst, "console=ttyS0", None).unwrap();
+
+        // Stand-in for `reseed_rng_seed`'s append: the point under
+        // test is that `/chosen` now exists for it to land in, not
+        // the `EFI_RNG_PROTOCOL` call this test has no handle for.
+        let seed = [0x42u8; 32];
+        append_chosen_property(&mut dst, "rng-seed", &seed).unwrap();
+
+        let (start, end) = raw::find_node(&dst, "/chosen").unwrap();
+        assert_eq!(
+            raw::read_property(&dst, start, end, "rng-seed"),
+            Some(&seed[..])
+        );
+    }
+
+    #[test]
+    fn write_boot_params_round_trips_bootargs_and_initrd_range() {
+        let src = raw::test_fdt_blob();
+        let mut dst = vec![0u8; src.len() + 512];
+        raw::relocate(&src, &mut dst);
+
+        write_boot_params(
+            &mut dst,
+            "console=ttyS0 root=/dev/sda1",
+            Some((0x4000_0000, 0x4100_0000)),
+        )
+        .unwrap();
+
+        let (start, end) = raw::find_node(&dst, "/chosen").unwrap();
+        assert_eq!(
+            raw::read_property(&dst, start, end, "bootargs"),
+            Some(&b"console=ttyS0 root=/dev/sda1\0"[..])
+        );
+        assert_eq!(
+            raw::read_property(&dst, start, end, "linux,initrd-start"),
+            Some(&0x4000_0000u64.to_be_bytes()[..])
+        );
+        assert_eq!(
+            raw::read_property(&dst, start, end, "linux,initrd-end"),
+            Some(&0x4100_0000u64.to_be_bytes()[..])
+        );
+    }
+}
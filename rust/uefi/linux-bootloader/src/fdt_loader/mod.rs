@@ -0,0 +1,273 @@
+//! This module sets the necessary tables to pass a device tree
+//! to the Linux kernel
+
+mod chosen;
+mod raw;
+mod reserved_memory;
+
+use core::ptr::{self, copy_nonoverlapping};
+use core::slice;
+
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use uefi::{
+    guid,
+    prelude::BootServices,
+    proto::unsafe_protocol,
+    table::boot::{AllocateType, MemoryType},
+    Guid, Handle, Result, Status, StatusExt,
+};
+
+use crate::uefi_helpers::{bytes_to_pages, UEFI_PAGE_BITS};
+
+/// GUID of the configuration table under which a device tree blob is
+/// published (by us, or by firmware for its own platform tree), as
+/// defined by the [UEFI
+/// specification](https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#industry-standard-configuration-tables).
+pub(crate) const DEVICE_TREE_GUID: Guid = guid!("b1b621d5-f19c-41a5-830b-d9152c69aae0");
+
+/// Minimum extra space requested beyond the incoming blob's own
+/// `totalsize`, so later in-place edits (stripped/added `/chosen`
+/// properties, memory reservations, ...) never run out of room.
+const GROW_HEADROOM: usize = 4 * 1024;
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+    /// Fixup flags as descrribed in https://github.com/U-Boot-EFI/EFI_DT_FIXUP_PROTOCOL
+    pub struct DTFixupFlags: u32 {
+        const APPLY_FIXUPS = 1 << 0;
+        const RESERVE_MEMORY = 1 << 1;
+    }
+}
+
+/// The device tree fixup protocol.
+///
+/// Device trees do not contain machine-specific information like
+/// serial numbers or MAC addresses out of the box. The firmware,
+/// usually U-Boot exposes this protocol to add such machine-specific
+/// options.
+///
+/// For more information see the [u-boot
+/// proposal](https://github.com/U-Boot-EFI/EFI_DT_FIXUP_PROTOCOL)
+#[unsafe_protocol("e617d64c-fe08-46da-f4dc-bbd5870c7300")]
+struct DTFixupProtocol {
+    pub fixup: unsafe extern "efiapi" fn(
+        this: *mut DTFixupProtocol,
+        fdt: *mut u8,
+        buffer_size: *mut usize,
+        flags: DTFixupFlags,
+    ) -> Status,
+}
+
+/// Returns the root node's `compatible` string-list property,
+/// most-specific match first, or `None` if `fdt_data` isn't a valid
+/// FDT or has no `compatible` property. Used to match an embedded
+/// devicetree against the firmware's own platform tree.
+pub fn root_compatible_strings(fdt_data: &[u8]) -> Option<Vec<&str>> {
+    if !raw::validate_header(fdt_data) {
+        return None;
+    }
+    raw::root_compatible(fdt_data)
+}
+
+/// Reads a device tree blob given only a pointer to its start, using
+/// the blob's own `totalsize` header field to determine its length.
+/// Returns `None` if the resulting header doesn't validate.
+///
+/// # Safety
+///
+/// `ptr` must be non-null and point to memory that is valid to read
+/// for at least as many bytes as the blob it contains claims to be.
+pub unsafe fn blob_from_ptr(ptr: *const u8) -> Option<&'static [u8]> {
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: upheld by the caller; we only peek the header here.
+    let header = unsafe { slice::from_raw_parts(ptr, 8) };
+    let totalsize = u32::from_be_bytes(header[4..8].try_into().ok()?) as usize;
+
+    // SAFETY: upheld by the caller, who promises `ptr` is valid for at
+    // least the blob's own claimed size.
+    let blob = unsafe { slice::from_raw_parts(ptr, totalsize) };
+    raw::validate_header(blob).then_some(blob)
+}
+
+/// Validates `fdt_data`'s header, then relocates it into a freshly
+/// page-allocated, `fdt_open_into`-style buffer with headroom for
+/// later in-place edits. The blob must live in memory the kernel can
+/// read after `ExitBootServices`, which a transient [`Vec`] doesn't
+/// guarantee. Returns the buffer's base and page count.
+fn prepare_fdt_buffer(boot_services: &BootServices, fdt_data: &[u8]) -> Result<(*mut u8, usize)> {
+    if !raw::validate_header(fdt_data) {
+        Status::INVALID_PARAMETER.to_result()?;
+    }
+
+    let requested_size = (raw::totalsize(fdt_data) as usize).saturating_add(GROW_HEADROOM);
+    let num_pages = bytes_to_pages(requested_size);
+    let base = boot_services.allocate_pages(
+        AllocateType::AnyPages,
+        MemoryType::ACPI_NON_VOLATILE,
+        num_pages,
+    )? as *mut u8;
+
+    // SAFETY: `base` was just allocated above for `num_pages` pages.
+    let dst = unsafe { slice::from_raw_parts_mut(base, num_pages << UEFI_PAGE_BITS) };
+    dst.fill(0);
+    raw::relocate(fdt_data, dst);
+
+    Ok((base, num_pages))
+}
+
+/// Fixup an already-allocated fdt buffer with a [`DTFixupProtocol`].
+///
+/// If the firmware reports the buffer as too small, allocates a
+/// larger one, copies the blob over, frees the old buffer and retries.
+/// Returns the base and page count of the (possibly reallocated)
+/// buffer.
+fn fixup_fdt(
+    boot_services: &BootServices,
+    fixup_handle: Handle,
+    mut base: *mut u8,
+    mut num_pages: usize,
+) -> Result<(*mut u8, usize)> {
+    let mut fixup_protocol =
+        boot_services.open_protocol_exclusive::<DTFixupProtocol>(fixup_handle)?;
+
+    let mut fdt_size = num_pages << UEFI_PAGE_BITS;
+
+    unsafe {
+        let status = (fixup_protocol.fixup)(
+            &mut *fixup_protocol,
+            base,
+            &mut fdt_size as *mut usize,
+            DTFixupFlags::APPLY_FIXUPS | DTFixupFlags::RESERVE_MEMORY,
+        );
+
+        if status.is_success() {
+            return Ok((base, num_pages));
+        }
+        if status != Status::BUFFER_TOO_SMALL {
+            boot_services.free_pages(base as u64, num_pages)?;
+            return status.to_result();
+        }
+
+        // Everything is fine except our buffer is too small, make a new bigger one
+        let old_base = base;
+        let old_pages = num_pages;
+        num_pages = bytes_to_pages(fdt_size);
+        fdt_size = num_pages << UEFI_PAGE_BITS;
+        base = boot_services.allocate_pages(
+            AllocateType::AnyPages,
+            MemoryType::ACPI_NON_VOLATILE,
+            num_pages,
+        )? as *mut u8;
+
+        copy_nonoverlapping(old_base, base, old_pages << UEFI_PAGE_BITS);
+        boot_services.free_pages(old_base as u64, old_pages)?;
+
+        (fixup_protocol.fixup)(
+            &mut *fixup_protocol,
+            base,
+            &mut fdt_size as *mut usize,
+            DTFixupFlags::APPLY_FIXUPS | DTFixupFlags::RESERVE_MEMORY,
+        )
+        .to_result()?;
+    }
+
+    Ok((base, num_pages))
+}
+
+/// A RAII wrapper that installs the device tree as a configuration
+/// table and removes that table entry again on uninstall. A device
+/// tree the firmware had published under the same GUID is replaced,
+/// and is not put back by [`FdtLoader::uninstall`].
+///
+/// **Note:** You need to call [`FdtLoader::uninstall`], before
+/// this is dropped.
+pub struct FdtLoader {
+    handle: Handle,
+    /// Base of the page-allocated buffer backing the installed blob.
+    base: *mut u8,
+    /// Number of pages in the buffer pointed to by `base`.
+    num_pages: usize,
+    set: bool,
+}
+
+impl FdtLoader {
+    /// Create a new [`FdtLoader`].
+    ///
+    /// `handle` is the handle where the protocols are registered on.
+    /// `cmdline` becomes `/chosen/bootargs`, and `initrd_range`, if
+    /// given, the physical `(start, end)` of the loaded initrd
+    /// becomes `/chosen/linux,initrd-{start,end}`. If no
+    /// [`DTFixupProtocol`] is present, the memory reservation block
+    /// and `/reserved-memory` are honored directly instead.
+    pub fn new(
+        boot_services: &BootServices,
+        handle: Handle,
+        fdt_data: Vec<u8>,
+        cmdline: &str,
+        initrd_range: Option<(u64, u64)>,
+    ) -> Result<Self> {
+        let (base, num_pages) = prepare_fdt_buffer(boot_services, &fdt_data)?;
+        drop(fdt_data);
+
+        // SAFETY: `base` was just allocated above for `num_pages` pages.
+        let buf = unsafe { slice::from_raw_parts_mut(base, num_pages << UEFI_PAGE_BITS) };
+        chosen::strip_kaslr_seed(buf);
+        chosen::write_boot_params(buf, cmdline, initrd_range)?;
+        // `/chosen` now exists (write_boot_params creates it if
+        // needed), so the reseed can actually land.
+        chosen::reseed_rng_seed(boot_services, buf);
+
+        let (base, num_pages) =
+            if let Ok(fixup_handle) = boot_services.get_handle_for_protocol::<DTFixupProtocol>() {
+                // The firmware already reserves these ranges itself,
+                // per the `RESERVE_MEMORY` flag passed below.
+                fixup_fdt(boot_services, fixup_handle, base, num_pages)?
+            } else {
+                reserved_memory::reserve_memory(boot_services, buf)?;
+                (base, num_pages)
+            };
+
+        unsafe {
+            boot_services.install_configuration_table(&DEVICE_TREE_GUID, base.cast())?;
+        }
+
+        Ok(FdtLoader {
+            handle,
+            base,
+            num_pages,
+            set: true,
+        })
+    }
+
+    pub fn uninstall(&mut self, boot_services: &BootServices) -> Result<()> {
+        // This should only be called once.
+        assert!(self.set);
+
+        // Mark ourselves torn down before the fallible calls below,
+        // not after: otherwise an early `?` return on error would
+        // leave `self.set` true, and the subsequent `Drop` would
+        // panic on top of the original error instead of propagating
+        // it cleanly.
+        self.set = false;
+
+        unsafe {
+            // A null table pointer removes the entry for `guid_entry`
+            // instead of replacing it, per the UEFI specification.
+            boot_services.install_configuration_table(&DEVICE_TREE_GUID, ptr::null::<u8>())?;
+            boot_services.free_pages(self.base as u64, self.num_pages)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for FdtLoader {
+    fn drop(&mut self) {
+        // Dropped without unregistering!
+        assert!(!self.set);
+    }
+}
@@ -0,0 +1,596 @@
+//! Minimal, in-place editing primitives for the flattened device
+//! tree (FDT) binary format.
+//!
+//! These operate directly on the bytes of an already-validated FDT
+//! blob living in a buffer that may have spare capacity beyond the
+//! header's `totalsize`; callers are responsible for making sure that
+//! capacity exists before inserting anything.
+
+use core::str;
+
+use alloc::vec::Vec;
+
+/// Size in bytes of the FDT header (10 big-endian `u32` fields).
+const HEADER_SIZE: usize = 40;
+
+const FDT_MAGIC: u32 = 0xd00d_feed;
+/// Oldest FDT version this loader understands, matching what the
+/// Linux EFI stub requires (`FDT_FIRST_SUPPORTED_VERSION`).
+const FDT_FIRST_SUPPORTED_VERSION: u32 = 16;
+/// Newest FDT version this loader understands
+/// (`FDT_LAST_SUPPORTED_VERSION`).
+const FDT_LAST_SUPPORTED_VERSION: u32 = 17;
+
+/// Extra space left between the end of the relocated struct block and
+/// the start of the strings block, in the spirit of `fdt_open_into`.
+/// The insert functions below do not consume it: they shift
+/// everything after the insertion point, this gap included, into the
+/// free space past `totalsize`.
+const STRUCT_GROW_HEADROOM: usize = 256;
+
+mod token {
+    pub const BEGIN_NODE: u32 = 0x1;
+    pub const END_NODE: u32 = 0x2;
+    pub const PROP: u32 = 0x3;
+    pub const NOP: u32 = 0x4;
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(buf: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
+
+fn write_u32(buf: &mut [u8], offset: usize, value: u32) {
+    buf[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+fn align8(offset: usize) -> usize {
+    (offset + 7) & !7
+}
+
+/// Reads a NUL-terminated string starting at `offset`.
+fn read_cstr(buf: &[u8], offset: usize) -> &str {
+    let len = buf[offset..]
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(buf.len() - offset);
+    str::from_utf8(&buf[offset..offset + len]).unwrap_or_default()
+}
+
+macro_rules! header_field {
+    ($get:ident, $set:ident, $offset:expr) => {
+        pub(super) fn $get(buf: &[u8]) -> u32 {
+            read_u32(buf, $offset)
+        }
+
+        pub(super) fn $set(buf: &mut [u8], value: u32) {
+            write_u32(buf, $offset, value)
+        }
+    };
+}
+
+header_field!(totalsize, set_totalsize, 4);
+header_field!(off_dt_struct, set_off_dt_struct, 8);
+header_field!(off_dt_strings, set_off_dt_strings, 12);
+header_field!(off_mem_rsvmap, set_off_mem_rsvmap, 16);
+header_field!(size_dt_strings, set_size_dt_strings, 32);
+header_field!(size_dt_struct, set_size_dt_struct, 36);
+
+fn magic(buf: &[u8]) -> u32 {
+    read_u32(buf, 0)
+}
+
+fn version(buf: &[u8]) -> u32 {
+    read_u32(buf, 20)
+}
+
+fn last_comp_version(buf: &[u8]) -> u32 {
+    read_u32(buf, 24)
+}
+
+/// Validates that `buf` starts with a well-formed FDT header: the
+/// magic number matches, `totalsize` fits within `buf`, the version
+/// is one we understand, and the struct/strings blocks it describes
+/// lie within `totalsize`. Mirrors the checks the Linux EFI stub
+/// performs before trusting a devicetree blob.
+pub(super) fn validate_header(buf: &[u8]) -> bool {
+    if buf.len() < HEADER_SIZE || magic(buf) != FDT_MAGIC {
+        return false;
+    }
+
+    let total = totalsize(buf) as usize;
+    if total > buf.len() || total < HEADER_SIZE {
+        return false;
+    }
+
+    if version(buf) < FDT_FIRST_SUPPORTED_VERSION
+        || last_comp_version(buf) > FDT_LAST_SUPPORTED_VERSION
+    {
+        return false;
+    }
+
+    let rsvmap_start = off_mem_rsvmap(buf) as usize;
+    let struct_start = off_dt_struct(buf) as usize;
+    let struct_end = struct_start.checked_add(size_dt_struct(buf) as usize);
+    let strings_start = off_dt_strings(buf) as usize;
+    let strings_end = strings_start.checked_add(size_dt_strings(buf) as usize);
+
+    struct_start % 4 == 0
+        && rsvmap_start >= HEADER_SIZE
+        && rsvmap_start <= struct_start
+        && matches!(struct_end, Some(end) if end <= total)
+        && matches!(strings_end, Some(end) if end <= total)
+}
+
+/// Re-lays out a validated FDT from `src` into the larger, zeroed
+/// `dst`, in the spirit of libfdt's `fdt_open_into`: the mem_rsvmap
+/// and struct blocks are packed at the front with some headroom
+/// before the strings block, and whatever remains of `dst` past the
+/// strings block becomes free space the insert functions can use.
+/// `totalsize` is set to the packed length, not `dst.len()`, so that
+/// free space stays visible as `dst.len() - totalsize`.
+///
+/// `src` must have already passed [`validate_header`], and `dst` must
+/// be at least as large as `src`'s `totalsize` plus the headroom
+/// needed for the relocated layout.
+pub(super) fn relocate(src: &[u8], dst: &mut [u8]) {
+    let old_rsvmap_start = off_mem_rsvmap(src) as usize;
+    let old_struct_start = off_dt_struct(src) as usize;
+    let struct_len = size_dt_struct(src) as usize;
+    let old_strings_start = off_dt_strings(src) as usize;
+    let strings_len = size_dt_strings(src) as usize;
+    let rsvmap_len = old_struct_start - old_rsvmap_start;
+
+    let rsvmap_start = HEADER_SIZE;
+    let struct_start = align8(rsvmap_start + rsvmap_len);
+    let strings_start = align4(struct_start + struct_len) + STRUCT_GROW_HEADROOM;
+
+    dst[..HEADER_SIZE].copy_from_slice(&src[..HEADER_SIZE]);
+    dst[rsvmap_start..rsvmap_start + rsvmap_len]
+        .copy_from_slice(&src[old_rsvmap_start..old_rsvmap_start + rsvmap_len]);
+    dst[struct_start..struct_start + struct_len]
+        .copy_from_slice(&src[old_struct_start..old_struct_start + struct_len]);
+    dst[strings_start..strings_start + strings_len]
+        .copy_from_slice(&src[old_strings_start..old_strings_start + strings_len]);
+
+    set_off_mem_rsvmap(dst, rsvmap_start as u32);
+    set_off_dt_struct(dst, struct_start as u32);
+    set_size_dt_struct(dst, struct_len as u32);
+    set_off_dt_strings(dst, strings_start as u32);
+    set_size_dt_strings(dst, strings_len as u32);
+    set_totalsize(dst, (strings_start + strings_len) as u32);
+}
+
+/// Returns the struct-block offset of this node's `FDT_END_NODE`
+/// token, given the offset of the first token inside it (i.e. right
+/// after its name).
+fn skip_node(buf: &[u8], mut offset: usize) -> Option<usize> {
+    loop {
+        match read_u32(buf, offset) {
+            token::BEGIN_NODE => {
+                let name_start = offset + 4;
+                let name = read_cstr(buf, name_start);
+                let body = align4(name_start + name.len() + 1);
+                offset = skip_node(buf, body)? + 4;
+            }
+            token::PROP => {
+                let len = read_u32(buf, offset + 4) as usize;
+                offset = align4(offset + 12 + len);
+            }
+            token::NOP => offset += 4,
+            token::END_NODE => return Some(offset),
+            _ => return None,
+        }
+    }
+}
+
+/// Locates the direct child node named `name`, scanning from `offset`
+/// (the first token inside the parent). Returns the same
+/// `(body, end)` shape as [`find_node`].
+fn find_child(buf: &[u8], mut offset: usize, name: &str) -> Option<(usize, usize)> {
+    loop {
+        match read_u32(buf, offset) {
+            token::BEGIN_NODE => {
+                let name_start = offset + 4;
+                let child_name = read_cstr(buf, name_start);
+                let body = align4(name_start + child_name.len() + 1);
+                let end = skip_node(buf, body)?;
+                if child_name == name {
+                    return Some((body, end));
+                }
+                offset = end + 4;
+            }
+            token::PROP => {
+                let len = read_u32(buf, offset + 4) as usize;
+                offset = align4(offset + 12 + len);
+            }
+            token::NOP => offset += 4,
+            _ => return None,
+        }
+    }
+}
+
+/// Locates the node at the absolute, slash-separated `path` (e.g.
+/// `/chosen`). Returns the struct-block offset of the first token
+/// inside the node and the offset of its `FDT_END_NODE` token, or
+/// `None` if any component of the path doesn't exist.
+pub(super) fn find_node(buf: &[u8], path: &str) -> Option<(usize, usize)> {
+    let struct_start = off_dt_struct(buf) as usize;
+    // The root node's own name is always the empty string.
+    let root_body = align4(struct_start + 4 + 1);
+    let mut node = (root_body, skip_node(buf, root_body)?);
+
+    for component in path.trim_matches('/').split('/').filter(|c| !c.is_empty()) {
+        node = find_child(buf, node.0, component)?;
+    }
+    Some(node)
+}
+
+/// Locates property `name` directly on the node spanning `[start,
+/// end)`, as returned by [`find_node`]. Returns the struct-block
+/// offset of its `FDT_PROP` token and the token's total on-wire size.
+pub(super) fn find_property(buf: &[u8], start: usize, end: usize, name: &str) -> Option<(usize, usize)> {
+    let mut offset = start;
+    while offset < end {
+        match read_u32(buf, offset) {
+            token::PROP => {
+                let len = read_u32(buf, offset + 4) as usize;
+                let nameoff = read_u32(buf, offset + 8) as usize;
+                let total = align4(12 + len);
+                if read_cstr(buf, off_dt_strings(buf) as usize + nameoff) == name {
+                    return Some((offset, total));
+                }
+                offset += total;
+            }
+            token::NOP => offset += 4,
+            token::BEGIN_NODE => {
+                let name_start = offset + 4;
+                let child_name = read_cstr(buf, name_start);
+                let body = align4(name_start + child_name.len() + 1);
+                offset = skip_node(buf, body)? + 4;
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Removes the property token at `offset` (of on-wire size `len`,
+/// as returned by [`find_property`]), shifting everything after it
+/// down and shrinking `totalsize` accordingly.
+pub(super) fn delete_property(buf: &mut [u8], offset: usize, len: usize) {
+    let used = totalsize(buf) as usize;
+    buf.copy_within(offset + len..used, offset);
+    buf[used - len..used].fill(0);
+
+    set_size_dt_struct(buf, size_dt_struct(buf) - len as u32);
+    set_off_dt_strings(buf, off_dt_strings(buf) - len as u32);
+    set_totalsize(buf, (used - len) as u32);
+}
+
+/// Finds `name` in the strings block, appending it if it isn't
+/// already there. Returns its offset relative to the strings block,
+/// or `None` if `buf` doesn't have room to append.
+fn ensure_string(buf: &mut [u8], name: &str) -> Option<u32> {
+    let strings_start = off_dt_strings(buf) as usize;
+    let strings_len = size_dt_strings(buf) as usize;
+
+    let mut offset = 0;
+    while offset < strings_len {
+        let s = read_cstr(buf, strings_start + offset);
+        if s == name {
+            return Some(offset as u32);
+        }
+        offset += s.len() + 1;
+    }
+
+    let needed = name.len() + 1;
+    let used = totalsize(buf) as usize;
+    if used + needed > buf.len() {
+        return None;
+    }
+
+    let insert_at = strings_start + strings_len;
+    buf.copy_within(insert_at..used, insert_at + needed);
+    buf[insert_at..insert_at + name.len()].copy_from_slice(name.as_bytes());
+    buf[insert_at + name.len()] = 0;
+
+    set_size_dt_strings(buf, (strings_len + needed) as u32);
+    set_totalsize(buf, (used + needed) as u32);
+
+    Some(strings_len as u32)
+}
+
+/// Appends a new, empty child node named `name` as the last child of
+/// the node whose `FDT_END_NODE` token is at `parent_end` (the `end`
+/// half of a [`find_node`] result). Returns the new child's `(body,
+/// end)`, equal to each other since it starts out empty. Returns
+/// `None` if `buf` doesn't have room.
+pub(super) fn create_node(buf: &mut [u8], parent_end: usize, name: &str) -> Option<(usize, usize)> {
+    let name_len = align4(name.len() + 1);
+    let node_len = 4 + name_len + 4;
+    let used = totalsize(buf) as usize;
+    if used + node_len > buf.len() {
+        return None;
+    }
+
+    buf.copy_within(parent_end..used, parent_end + node_len);
+    write_u32(buf, parent_end, token::BEGIN_NODE);
+    let name_start = parent_end + 4;
+    buf[name_start..name_start + name.len()].copy_from_slice(name.as_bytes());
+    buf[name_start + name.len()..name_start + name_len].fill(0);
+    let end_node_offset = name_start + name_len;
+    write_u32(buf, end_node_offset, token::END_NODE);
+
+    set_size_dt_struct(buf, size_dt_struct(buf) + node_len as u32);
+    set_off_dt_strings(buf, off_dt_strings(buf) + node_len as u32);
+    set_totalsize(buf, (used + node_len) as u32);
+
+    Some((end_node_offset, end_node_offset))
+}
+
+/// Returns the root node's `compatible` string-list property,
+/// most-specific match first, or `None` if it isn't present.
+pub(super) fn root_compatible(buf: &[u8]) -> Option<Vec<&str>> {
+    let (start, end) = find_node(buf, "/")?;
+    let (offset, _) = find_property(buf, start, end, "compatible")?;
+    let len = read_u32(buf, offset + 4) as usize;
+    let value_start = offset + 12;
+
+    Some(
+        buf[value_start..value_start + len]
+            .split(|&b| b == 0)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| str::from_utf8(s).ok())
+            .collect(),
+    )
+}
+
+/// Returns the root node's `#address-cells` property value, or
+/// `None` if it isn't present (callers should fall back to the
+/// devicetree specification's default of 2 for the root node).
+pub(super) fn root_address_cells(buf: &[u8]) -> Option<u32> {
+    let (start, end) = find_node(buf, "/")?;
+    let (offset, _) = find_property(buf, start, end, "#address-cells")?;
+    Some(read_u32(buf, offset + 12))
+}
+
+/// Returns the root node's `#size-cells` property value, or `None`
+/// if it isn't present (callers should fall back to the devicetree
+/// specification's default of 1 for the root node).
+pub(super) fn root_size_cells(buf: &[u8]) -> Option<u32> {
+    let (start, end) = find_node(buf, "/")?;
+    let (offset, _) = find_property(buf, start, end, "#size-cells")?;
+    Some(read_u32(buf, offset + 12))
+}
+
+/// Returns the value bytes of property `name` directly on the node
+/// spanning `[start, end)`, or `None` if it isn't present.
+pub(super) fn read_property<'a>(buf: &'a [u8], start: usize, end: usize, name: &str) -> Option<&'a [u8]> {
+    let (offset, _) = find_property(buf, start, end, name)?;
+    let len = read_u32(buf, offset + 4) as usize;
+    let value_start = offset + 12;
+    Some(&buf[value_start..value_start + len])
+}
+
+/// Returns the `(address, size)` pairs of the memory reservation
+/// block, stopping at the zero-address-and-size terminator entry as
+/// required by the devicetree specification. Also stops, without
+/// panicking, if the block runs into the struct block without ever
+/// finding one, which [`validate_header`] doesn't itself rule out.
+pub(super) fn mem_reservations(buf: &[u8]) -> Vec<(u64, u64)> {
+    let end = off_dt_struct(buf) as usize;
+    let mut offset = off_mem_rsvmap(buf) as usize;
+    let mut out = Vec::new();
+    while offset + 16 <= end {
+        let address = read_u64(buf, offset);
+        let size = read_u64(buf, offset + 8);
+        if address == 0 && size == 0 {
+            return out;
+        }
+        out.push((address, size));
+        offset += 16;
+    }
+    out
+}
+
+/// Direct child node spans of the node whose first token is at
+/// `offset`, in the same `(body, end)` shape as [`find_node`].
+pub(super) fn child_spans(buf: &[u8], mut offset: usize) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    loop {
+        match read_u32(buf, offset) {
+            token::BEGIN_NODE => {
+                let name_start = offset + 4;
+                let name = read_cstr(buf, name_start);
+                let body = align4(name_start + name.len() + 1);
+                let Some(end) = skip_node(buf, body) else {
+                    return out;
+                };
+                out.push((body, end));
+                offset = end + 4;
+            }
+            token::PROP => {
+                let len = read_u32(buf, offset + 4) as usize;
+                offset = align4(offset + 12 + len);
+            }
+            token::NOP => offset += 4,
+            _ => return out,
+        }
+    }
+}
+
+/// Appends property `name` = `value` just before the `FDT_END_NODE`
+/// token at `node_end` (the `end` half of a [`find_node`] result).
+/// Returns `None` if `buf` doesn't have room.
+pub(super) fn append_property(buf: &mut [u8], node_end: usize, name: &str, value: &[u8]) -> Option<()> {
+    let nameoff = ensure_string(buf, name)?;
+
+    let padded_len = align4(value.len());
+    let prop_len = 12 + padded_len;
+    let used = totalsize(buf) as usize;
+    if used + prop_len > buf.len() {
+        return None;
+    }
+
+    buf.copy_within(node_end..used, node_end + prop_len);
+    write_u32(buf, node_end, token::PROP);
+    write_u32(buf, node_end + 4, value.len() as u32);
+    write_u32(buf, node_end + 8, nameoff);
+    buf[node_end + 12..node_end + 12 + value.len()].copy_from_slice(value);
+    buf[node_end + 12 + value.len()..node_end + prop_len].fill(0);
+
+    set_size_dt_struct(buf, size_dt_struct(buf) + prop_len as u32);
+    set_off_dt_strings(buf, off_dt_strings(buf) + prop_len as u32);
+    set_totalsize(buf, (used + prop_len) as u32);
+
+    Some(())
+}
+
+/// Builds a minimal, compact (no spare headroom of its own) FDT
+/// blob: an empty root node with just a `compatible` property.
+/// Exposed to sibling modules' tests so they can exercise edits on a
+/// real, [`relocate`]d blob without hand-rolling bytes themselves.
+#[cfg(test)]
+pub(super) fn test_fdt_blob() -> Vec<u8> {
+    let strings = b"compatible\0";
+    let value = b"vendor,board\0";
+
+    let rsvmap_start = HEADER_SIZE;
+    let rsvmap_len = 16; // a single zero terminator entry
+    let struct_start = rsvmap_start + rsvmap_len;
+
+    let name_len = align4(1); // root's name is the empty string
+    let prop_len = 12 + align4(value.len());
+    let struct_len = 4 + name_len + prop_len + 4 + 4; // + END_NODE + FDT_END
+
+    let strings_start = align4(struct_start + struct_len);
+    let strings_len = strings.len();
+    let total = strings_start + strings_len;
+
+    let mut buf = alloc::vec![0u8; total];
+    write_u32(&mut buf, 0, FDT_MAGIC);
+    write_u32(&mut buf, 4, total as u32);
+    write_u32(&mut buf, 8, struct_start as u32);
+    write_u32(&mut buf, 12, strings_start as u32);
+    write_u32(&mut buf, 16, rsvmap_start as u32);
+    write_u32(&mut buf, 20, FDT_LAST_SUPPORTED_VERSION);
+    write_u32(&mut buf, 24, FDT_FIRST_SUPPORTED_VERSION);
+    write_u32(&mut buf, 32, strings_len as u32);
+    write_u32(&mut buf, 36, struct_len as u32);
+
+    let mut offset = struct_start;
+    write_u32(&mut buf, offset, token::BEGIN_NODE);
+    offset += 4 + name_len; // name bytes are already zeroed (empty string)
+    write_u32(&mut buf, offset, token::PROP);
+    write_u32(&mut buf, offset + 4, value.len() as u32);
+    write_u32(&mut buf, offset + 8, 0); // "compatible" is the only, first string
+    buf[offset + 12..offset + 12 + value.len()].copy_from_slice(value);
+    offset += prop_len;
+    write_u32(&mut buf, offset, token::END_NODE);
+    offset += 4;
+    write_u32(&mut buf, offset, 9); // FDT_END
+
+    buf[strings_start..strings_start + strings_len].copy_from_slice(strings);
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn validate_header_accepts_test_blob() {
+        assert!(validate_header(&test_fdt_blob()));
+    }
+
+    #[test]
+    fn validate_header_rejects_bad_magic() {
+        let mut buf = test_fdt_blob();
+        buf[0] = 0;
+        assert!(!validate_header(&buf));
+    }
+
+    #[test]
+    fn find_node_reads_root_compatible() {
+        let buf = test_fdt_blob();
+        assert_eq!(root_compatible(&buf), Some(vec!["vendor,board"]));
+    }
+
+    /// Regression test for the bug a maintainer review caught:
+    /// `relocate` used to set `totalsize` to the whole buffer instead
+    /// of the packed length, leaving zero headroom detectable by
+    /// `append_property`/`create_node`, so every insert into a
+    /// relocated blob failed with `None`.
+    #[test]
+    fn relocate_leaves_real_headroom_for_inserts() {
+        let src = test_fdt_blob();
+        let mut dst = vec![0u8; src.len() + 512];
+        relocate(&src, &mut dst);
+
+        assert!(dst.len() as u32 > totalsize(&dst));
+
+        let (_, root_end) = find_node(&dst, "/").unwrap();
+        append_property(&mut dst, root_end, "model", b"test\0").unwrap();
+
+        let (start, end) = find_node(&dst, "/").unwrap();
+        assert_eq!(read_property(&dst, start, end, "model"), Some(&b"test\0"[..]));
+    }
+
+    #[test]
+    fn create_node_and_delete_property_round_trip() {
+        let src = test_fdt_blob();
+        let mut dst = vec![0u8; src.len() + 512];
+        relocate(&src, &mut dst);
+
+        let (_, root_end) = find_node(&dst, "/").unwrap();
+        let (_, chosen_end) = create_node(&mut dst, root_end, "chosen").unwrap();
+        append_property(&mut dst, chosen_end, "bootargs", b"console=ttyS0\0").unwrap();
+
+        let (start, end) = find_node(&dst, "/chosen").unwrap();
+        assert_eq!(
+            read_property(&dst, start, end, "bootargs"),
+            Some(&b"console=ttyS0\0"[..])
+        );
+
+        let (offset, len) = find_property(&dst, start, end, "bootargs").unwrap();
+        delete_property(&mut dst, offset, len);
+        assert!(find_property(&dst, start, end, "bootargs").is_none());
+    }
+
+    #[test]
+    fn mem_reservations_reads_a_reserved_range() {
+        let mut buf = test_fdt_blob();
+        let rsvmap_start = off_mem_rsvmap(&buf) as usize;
+        write_u32(&mut buf, rsvmap_start, 0);
+        write_u32(&mut buf, rsvmap_start + 4, 0x1000);
+        write_u32(&mut buf, rsvmap_start + 8, 0);
+        write_u32(&mut buf, rsvmap_start + 12, 0x2000);
+
+        assert_eq!(mem_reservations(&buf), vec![(0x1000, 0x2000)]);
+    }
+
+    #[test]
+    fn mem_reservations_does_not_panic_without_terminator() {
+        let mut buf = test_fdt_blob();
+        let rsvmap_start = off_mem_rsvmap(&buf) as usize;
+        // Non-zero, non-terminating entry right up against the
+        // struct block, with no terminator before it: must stop
+        // instead of reading (and panicking) past `off_dt_struct`.
+        write_u32(&mut buf, rsvmap_start, 0);
+        write_u32(&mut buf, rsvmap_start + 4, 1);
+        write_u32(&mut buf, rsvmap_start + 8, 0);
+        write_u32(&mut buf, rsvmap_start + 12, 1);
+
+        let _ = mem_reservations(&buf);
+    }
+}
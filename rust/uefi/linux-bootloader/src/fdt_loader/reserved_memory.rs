@@ -0,0 +1,153 @@
+//! Registers the ranges named by the FDT's memory reservation block
+//! and its `/reserved-memory` node with the UEFI memory map, so
+//! firmware and kernel agree on which regions are off-limits even
+//! when no [`super::DTFixupProtocol`] is present to do so for us.
+
+use alloc::vec::Vec;
+
+use uefi::{
+    prelude::BootServices,
+    table::boot::{AllocateType, MemoryType},
+    Result, Status,
+};
+
+use super::raw;
+use crate::uefi_helpers::{bytes_to_pages, UEFI_PAGE_MASK};
+
+/// Allocates every range named by `buf`'s memory reservation block
+/// and its `/reserved-memory` child nodes, marking them off-limits
+/// in the UEFI memory map. Children without `no-map` are reserved as
+/// ordinary allocated memory (`BOOT_SERVICES_DATA`) rather than
+/// `RESERVED`, matching the devicetree specification's distinction
+/// between "don't create a mapping" and "just don't use it".
+pub(super) fn reserve_memory(boot_services: &BootServices, buf: &[u8]) -> Result<()> {
+    for (address, size) in raw::mem_reservations(buf) {
+        allocate_range(boot_services, address, size, MemoryType::RESERVED)?;
+    }
+
+    let Some((start, _)) = raw::find_node(buf, "/reserved-memory") else {
+        return Ok(());
+    };
+
+    // The devicetree specification defaults both cell counts to 2
+    // and 1 respectively when absent from the root node.
+    let address_cells = raw::root_address_cells(buf).unwrap_or(2).clamp(1, 2);
+    let size_cells = raw::root_size_cells(buf).unwrap_or(1).clamp(1, 2);
+
+    for (child_start, child_end) in raw::child_spans(buf, start) {
+        let Some(reg) = raw::read_property(buf, child_start, child_end, "reg") else {
+            continue;
+        };
+        let no_map = raw::read_property(buf, child_start, child_end, "no-map").is_some();
+        let memory_type = if no_map {
+            MemoryType::RESERVED
+        } else {
+            MemoryType::BOOT_SERVICES_DATA
+        };
+
+        for (address, size) in decode_reg(reg, address_cells, size_cells) {
+            allocate_range(boot_services, address, size, memory_type)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes a `reg` property's value into `(address, size)` pairs
+/// per the given cell counts, skipping malformed trailing bytes that
+/// don't fill a whole entry.
+fn decode_reg(reg: &[u8], address_cells: u32, size_cells: u32) -> Vec<(u64, u64)> {
+    let address_len = (address_cells * 4) as usize;
+    let entry_len = address_len + (size_cells * 4) as usize;
+
+    reg.chunks_exact(entry_len)
+        .map(|entry| {
+            let (address, size) = entry.split_at(address_len);
+            (read_be_cells(address), read_be_cells(size))
+        })
+        .collect()
+}
+
+fn read_be_cells(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, &byte| (acc << 8) | byte as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn decode_reg_with_two_address_cells_and_one_size_cell() {
+        let mut reg = Vec::new();
+        reg.extend_from_slice(&0x0000_0001_8000_0000u64.to_be_bytes());
+        reg.extend_from_slice(&0x0010_0000u32.to_be_bytes());
+
+        assert_eq!(decode_reg(&reg, 2, 1), vec![(0x1_8000_0000, 0x0010_0000)]);
+    }
+
+    #[test]
+    fn decode_reg_with_one_address_cell_and_one_size_cell() {
+        let mut reg = Vec::new();
+        reg.extend_from_slice(&0x8000_0000u32.to_be_bytes());
+        reg.extend_from_slice(&0x0010_0000u32.to_be_bytes());
+
+        assert_eq!(decode_reg(&reg, 1, 1), vec![(0x8000_0000, 0x0010_0000)]);
+    }
+
+    #[test]
+    fn decode_reg_reads_multiple_entries() {
+        let mut reg = Vec::new();
+        for (address, size) in [(0x1000u32, 0x1000u32), (0x2000, 0x2000)] {
+            reg.extend_from_slice(&address.to_be_bytes());
+            reg.extend_from_slice(&size.to_be_bytes());
+        }
+
+        assert_eq!(
+            decode_reg(&reg, 1, 1),
+            vec![(0x1000, 0x1000), (0x2000, 0x2000)]
+        );
+    }
+
+    #[test]
+    fn decode_reg_ignores_a_trailing_partial_entry() {
+        let mut reg = Vec::new();
+        reg.extend_from_slice(&0x1000u32.to_be_bytes());
+        reg.extend_from_slice(&0x1000u32.to_be_bytes());
+        reg.extend_from_slice(&[0u8; 3]); // short trailing garbage
+
+        assert_eq!(decode_reg(&reg, 1, 1), vec![(0x1000, 0x1000)]);
+    }
+}
+
+/// Allocates the page(s) covering `[address, address + size)`, a
+/// no-op for zero-size entries since those name no range at all.
+///
+/// `AllocateType::Address` requires a page-aligned address, which a
+/// `reg` entry is free to not be (its values are byte-granular), so
+/// the range is first rounded out to whole pages.
+fn allocate_range(
+    boot_services: &BootServices,
+    address: u64,
+    size: u64,
+    memory_type: MemoryType,
+) -> Result<()> {
+    if size == 0 {
+        return Ok(());
+    }
+
+    let aligned_start = address & !(UEFI_PAGE_MASK as u64);
+    let covered = (address - aligned_start) + size;
+    let pages = bytes_to_pages(covered as usize);
+    match boot_services.allocate_pages(AllocateType::Address(aligned_start), memory_type, pages) {
+        Ok(_) => Ok(()),
+        // The firmware (or an earlier pass over this same blob) may
+        // have already reserved this exact range, which is the usual
+        // case for `/memreserve/` and `/reserved-memory` entries on
+        // a platform whose firmware already honors them; that's the
+        // outcome we wanted, not an inconsistency.
+        Err(err) if err.status() == Status::NOT_FOUND => Ok(()),
+        Err(err) => Err(err),
+    }
+}
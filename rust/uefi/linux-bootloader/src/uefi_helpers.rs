@@ -1,5 +1,6 @@
 use core::ffi::c_void;
 
+use alloc::vec::Vec;
 use uefi::{prelude::BootServices, proto::loaded_image::LoadedImage, Result};
 
 #[derive(Debug, Clone, Copy)]
@@ -8,6 +9,28 @@ pub struct PeInMemory {
     image_size: usize,
 }
 
+/// A named section of a loaded PE image, as exposed by
+/// [`PeInMemory::sections`].
+#[derive(Debug, Clone, Copy)]
+pub struct PeSection<'a> {
+    name: [u8; PE_SECTION_NAME_LEN],
+    /// The section's bytes, as loaded at its virtual address.
+    pub data: &'a [u8],
+}
+
+impl PeSection<'_> {
+    /// The section's name, e.g. `.dtb`. PE section names are at most
+    /// 8 bytes and NUL-padded, so this is never more than 8 `char`s.
+    pub fn name(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.name.len());
+        core::str::from_utf8(&self.name[..len]).unwrap_or_default()
+    }
+}
+
 impl PeInMemory {
     /// Return a reference to the currently running image.
     ///
@@ -22,6 +45,77 @@ impl PeInMemory {
     pub unsafe fn as_slice(&self) -> &'static [u8] {
         unsafe { core::slice::from_raw_parts(self.image_base as *const u8, self.image_size) }
     }
+
+    /// Enumerates the running image's PE section table, returning
+    /// each section's name and its bytes as loaded at its virtual
+    /// address.
+    ///
+    /// Returns an empty list if the image doesn't parse as a PE file,
+    /// which shouldn't happen for an image the firmware itself loaded
+    /// and is executing.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`PeInMemory::as_slice`].
+    pub unsafe fn sections(&self) -> Vec<PeSection<'static>> {
+        let image = unsafe { self.as_slice() };
+        parse_pe_sections(image)
+    }
+}
+
+/// Offset of the `e_lfanew` field (the PE header's file offset) in
+/// the DOS header.
+const DOS_HEADER_LFANEW_OFFSET: usize = 0x3c;
+const PE_SIGNATURE: [u8; 4] = *b"PE\0\0";
+/// Size of the COFF file header that follows the PE signature.
+const COFF_HEADER_SIZE: usize = 20;
+const SECTION_HEADER_SIZE: usize = 40;
+const PE_SECTION_NAME_LEN: usize = 8;
+
+fn read_u16(buf: &[u8], offset: usize) -> Option<u16> {
+    buf.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Option<u32> {
+    buf.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+}
+
+/// Parses the PE section table out of a loaded image's bytes. Each
+/// section's data is sliced out at its virtual address, which is
+/// valid for an already-loaded image (as opposed to `PointerToRawData`,
+/// which addresses the file on disk).
+fn parse_pe_sections(image: &[u8]) -> Vec<PeSection<'_>> {
+    let Some(pe_offset) = read_u32(image, DOS_HEADER_LFANEW_OFFSET).map(|v| v as usize) else {
+        return Vec::new();
+    };
+    if image.get(pe_offset..pe_offset + 4) != Some(&PE_SIGNATURE[..]) {
+        return Vec::new();
+    }
+
+    let coff_header = pe_offset + 4;
+    let Some(num_sections) = read_u16(image, coff_header + 2) else {
+        return Vec::new();
+    };
+    let Some(optional_header_size) = read_u16(image, coff_header + 16) else {
+        return Vec::new();
+    };
+    let section_table = coff_header + COFF_HEADER_SIZE + optional_header_size as usize;
+
+    (0..num_sections as usize)
+        .filter_map(|i| {
+            let header = section_table + i * SECTION_HEADER_SIZE;
+            let mut name = [0u8; PE_SECTION_NAME_LEN];
+            name.copy_from_slice(image.get(header..header + PE_SECTION_NAME_LEN)?);
+
+            let virtual_size = read_u32(image, header + 8)? as usize;
+            let virtual_address = read_u32(image, header + 12)? as usize;
+            let data = image.get(virtual_address..virtual_address.checked_add(virtual_size)?)?;
+
+            Some(PeSection { name, data })
+        })
+        .collect()
 }
 
 /// Open the currently executing image as a file.